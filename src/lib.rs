@@ -6,6 +6,36 @@
 //!
 //! Because the internal buffer is fixed, lines longer than the buffer will be
 //! split.
+//! [`with_growth`](LineReader::with_growth) lifts this: the buffer then
+//! doubles as needed up to a given maximum, so lines that fit within that
+//! maximum come back whole.
+//!
+//! The `std` feature is enabled by default and brings in `std::io`'s
+//! `Read`/`BufRead`/`Seek` machinery. Disabling it (`default-features = false`)
+//! switches `LineReader` to a minimal `core`-only [`io::Read`] trait and
+//! `alloc`'s `Vec`, for use on `no_std` targets; the `next_line`/`next_batch`/
+//! `for_each` surface is identical either way, but `std`-only extras
+//! (the `Read`/`BufRead` impls on `LineReader`, `next_line_back`,
+//! [`PrefetchReader`]) are unavailable without `std`.
+//!
+//! This no_std support is built on the crate's own `io` module rather than
+//! the `core_io` crate: `core_io` is unmaintained and its build script
+//! doesn't run on current rustc, so it can't be a real dependency here.
+//! There's no maintained drop-in replacement for it either, so rather than
+//! depend on something broken, `no_std` mode just re-implements the sliver
+//! of `Read` this crate actually needs.
+//!
+//! Because `LineReader` itself implements `Read` and `BufRead` (with
+//! `std`), it composes like any other layered reader: wrap a
+//! decompressing reader in it for delimiter-scanned decompressed output, or
+//! wrap one `LineReader` in another to change delimiter and buffer size
+//! between sections of the same stream. There's no separate adaptor trait
+//! for this — `Read` already is that trait in this ecosystem, and every
+//! constructor (`new`, `with_capacity`, ...) already accepts anything that
+//! implements it, so a second one would just duplicate `Read` for no
+//! benefit. [`reset_with`](LineReader::reset_with) handles the narrower
+//! case of changing delimiter mid-stream on a single `LineReader` without
+//! losing already-buffered bytes.
 
 /*
 128k blocks:        0 lines 31603121046 bytes in  36.85s (817.92 MB/s)
@@ -15,9 +45,74 @@ read_line:  501636842 lines 31603121046 bytes in 139.14s (216.61 MB/s)
 lines():    501636842 lines 30599847362 bytes in 167.17s (174.57 MB/s)
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
 use std::cmp;
-use std::io;
-use std::io::ErrorKind;
+#[cfg(not(feature = "std"))]
+use core::cmp;
+
+#[cfg(feature = "std")]
+use std::mem;
+
+/// Re-exports (under the `std` feature) or re-implements (without it) the
+/// slice of `std::io` that `LineReader` needs: `Read`, `Result`, and
+/// `ErrorKind::Interrupted`. Everything in this crate reads through `io::*`
+/// so the same `refill` state machine works whether the reader is a `File`
+/// or a bare `core`-only byte source.
+#[cfg(feature = "std")]
+pub mod io {
+    pub use std::io::{BufRead, Error, ErrorKind, Read, Result, Seek, SeekFrom};
+}
+
+#[cfg(not(feature = "std"))]
+pub mod io {
+    //! A minimal `core`-only stand-in for the parts of `std::io` that
+    //! `LineReader` needs when built without the `std` feature.
+    use core::fmt;
+
+    /// The kinds of read failure `LineReader` itself cares about.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ErrorKind {
+        Interrupted,
+        Other,
+    }
+
+    /// A minimal read error, carrying only a `kind()` since there's no
+    /// `std::error::Error`/allocator-backed message to attach here.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Error(ErrorKind);
+
+    impl Error {
+        pub fn new(kind: ErrorKind) -> Self {
+            Error(kind)
+        }
+
+        pub fn kind(&self) -> ErrorKind {
+            self.0
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// The `core`-compatible read trait `LineReader` is generic over when
+    /// the `std` feature is disabled.
+    pub trait Read {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+    }
+}
 
 extern crate memchr;
 use memchr::{memchr, memrchr};
@@ -34,9 +129,72 @@ pub struct LineReader<R> {
     pos: usize,
     end_of_complete: usize,
     end_of_buffer: usize,
+    max_line: Option<usize>,
+    line_len: usize,
+    grow_to: Option<usize>,
+    // Set once `next_line_ext` has been called, so `refill` only pays for
+    // its EOF-disambiguation probe (see `pending`) when something actually
+    // consumes the Truncated/Unterminated distinction it exists for.
+    ext_mode: bool,
+    // A byte already consumed from `inner` by `refill`'s EOF-disambiguation
+    // probe, held until the next `refill` call folds it back in.
+    pending: Option<u8>,
+    #[cfg(feature = "std")]
+    rev: Option<ReverseState>,
+}
+
+/// State for the reverse (tail-first) scan done by `next_line_back`.
+///
+/// `block` holds the most recently read chunk. `end` is the search cursor:
+/// everything in `block[end..]` has already been matched to a delimiter and
+/// must not be searched again. `line_end` is the exclusive end boundary
+/// (delimiter included) of the line currently being assembled, which lags
+/// one step behind `end` — it still points past the delimiter `end` was
+/// just advanced to exclude, which is exactly the byte a returned line
+/// needs to keep. `offset` is the stream position of `block[0]`, and
+/// `carry` holds the tail of a line that was split across a block boundary
+/// until the rest of it turns up in an earlier block.
+#[cfg(feature = "std")]
+struct ReverseState {
+    block: Vec<u8>,
+    end: usize,
+    line_end: usize,
+    offset: u64,
+    carry: Vec<u8>,
+    line: Vec<u8>,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl ReverseState {
+    fn new(offset: u64) -> Self {
+        ReverseState {
+            block: Vec::new(),
+            end: 0,
+            line_end: 0,
+            offset,
+            carry: Vec::new(),
+            line: Vec::new(),
+            done: false,
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 use std::fmt;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+/// The outcome of a single `next_line_ext` call: a returned slice is either
+/// a complete, delimiter-terminated line, one chunk of a line too long to
+/// fit the buffer at once (more of the same line follows), or the final
+/// line of a stream that didn't end in the delimiter.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Complete(&'a [u8]),
+    Truncated(&'a [u8]),
+    Unterminated(&'a [u8]),
+}
 
 impl<R: io::Read> fmt::Debug for LineReader<R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -56,6 +214,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// let reader = LineReader::new(File::open("myfile.txt")?);
     /// # Ok(())
@@ -72,6 +231,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// let mut reader = LineReader::with_capacity(1024*512, File::open("myfile.txt")?);
     /// # Ok(())
@@ -88,6 +248,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// let mut reader = LineReader::with_delimiter(b'\t', File::open("myfile.txt")?);
     /// # Ok(())
@@ -104,6 +265,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// let mut reader = LineReader::with_delimiter_and_capacity(b'\t', 1024*512, File::open("myfile.txt")?);
     /// # Ok(())
@@ -113,10 +275,95 @@ impl<R: io::Read> LineReader<R> {
         Self {
             inner,
             delimiter,
-            buf: vec![0; capacity],
+            buf: Self::new_buf(capacity),
             pos: 0,
             end_of_complete: 0,
             end_of_buffer: 0,
+            max_line: None,
+            line_len: 0,
+            grow_to: None,
+            ext_mode: false,
+            pending: None,
+            #[cfg(feature = "std")]
+            rev: None,
+        }
+    }
+
+    /// Cap the length of a single logical line (the concatenation of every
+    /// `Truncated` chunk `next_line_ext` returns for it) at `max` bytes.
+    ///
+    /// Once a line exceeds this, the rest of it — up to and including its
+    /// delimiter — is discarded rather than handed back chunk by chunk, and
+    /// `next_line_ext` reports the total number of discarded bytes as an
+    /// error instead. This has no effect on `next_line`/`next_batch`, which
+    /// keep returning truncated slices with no length limit.
+    ///
+    /// ```no_run
+    /// # use linereader::LineReader;
+    /// # use std::fs::File;
+    /// # use std::io;
+    /// # #[cfg(feature = "std")]
+    /// # fn x() -> io::Result<()> {
+    /// let mut reader = LineReader::new(File::open("myfile.txt")?).with_max_line(1024 * 1024);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_max_line(mut self, max: usize) -> Self {
+        self.max_line = Some(max);
+        self
+    }
+
+    /// Allow the internal buffer to grow past its initial capacity
+    /// (doubling each time, up to `max_capacity`) instead of truncating
+    /// lines that don't fit in it.
+    ///
+    /// Without this, a line longer than the buffer is handed back in
+    /// `Truncated` chunks (`next_line`/`next_batch` silently, `next_line_ext`
+    /// explicitly via [`Line::Truncated`]) — the buffer never grows. With
+    /// it, capacity only grows when a line actually needs it, so the common
+    /// case still runs at the original capacity and only the rare oversized
+    /// line pays for a bigger allocation.
+    ///
+    /// ```no_run
+    /// # use linereader::LineReader;
+    /// # use std::fs::File;
+    /// # use std::io;
+    /// # #[cfg(feature = "std")]
+    /// # fn x() -> io::Result<()> {
+    /// let mut reader = LineReader::new(File::open("myfile.txt")?).with_growth(16 * 1024 * 1024);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_growth(mut self, max_capacity: usize) -> Self {
+        self.grow_to = Some(max_capacity);
+        self
+    }
+
+    #[allow(clippy::uninit_vec)]
+    fn new_buf(capacity: usize) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(capacity);
+        // SAFETY: bytes are only ever read out of `buf` up to `end_of_buffer`,
+        // and `end_of_buffer` only ever advances past bytes `inner.read()` has
+        // just written. The spare capacity beyond that is never inspected, so
+        // treating it as initialized is sound — `u8` has no invalid bit
+        // patterns, so there's nothing to uphold beyond "don't read before
+        // writing", which the rest of this type already guarantees.
+        unsafe {
+            buf.set_len(capacity);
+        }
+        buf
+    }
+
+    /// Grow `buf` to `new_len` without zeroing the new spare capacity; see
+    /// the safety note on `new_buf`.
+    #[allow(clippy::uninit_vec)]
+    fn grow_buf(&mut self, new_len: usize) {
+        debug_assert!(new_len >= self.buf.len());
+        self.buf.reserve(new_len - self.buf.len());
+        // SAFETY: same invariant as `new_buf` — nothing reads past
+        // `end_of_buffer`, which never exceeds the amount actually written.
+        unsafe {
+            self.buf.set_len(new_len);
         }
     }
 
@@ -128,6 +375,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// let buf: &[u8] = b"foo\nbar\nbaz";
     /// let mut reader = LineReader::new(buf);
@@ -161,6 +409,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// # let mut reader = LineReader::new(File::open("myfile.txt")?);
     /// while let Some(line) = reader.next_line() {
@@ -205,6 +454,7 @@ impl<R: io::Read> LineReader<R> {
     /// # use linereader::LineReader;
     /// # use std::fs::File;
     /// # use std::io;
+    /// # #[cfg(feature = "std")]
     /// # fn x() -> io::Result<()> {
     /// # let mut reader = LineReader::new(File::open("myfile.txt")?);
     /// while let Some(lines) = reader.next_batch() {
@@ -234,57 +484,223 @@ impl<R: io::Read> LineReader<R> {
         }
     }
 
+    /// Get the next line from the reader like `next_line`, but distinguish
+    /// *why* a returned slice doesn't end in the delimiter.
+    ///
+    /// `next_line` silently returns a plain slice whether it's a genuine
+    /// delimiter-terminated line, a chunk of a line too long to fit the
+    /// buffer in one go, or the final line of a stream with no trailing
+    /// delimiter. `next_line_ext` tells them apart via [`Line`]. If
+    /// [`with_max_line`](Self::with_max_line) was used and a single
+    /// logical line's `Truncated` chunks add up past the configured limit,
+    /// the remainder of that line (through its delimiter) is discarded and
+    /// reported as an error instead of being handed back piecemeal.
+    ///
+    /// ```no_run
+    /// # use linereader::{Line, LineReader};
+    /// # use std::fs::File;
+    /// # use std::io;
+    /// # #[cfg(feature = "std")]
+    /// # fn x() -> io::Result<()> {
+    /// # let mut reader = LineReader::new(File::open("myfile.txt")?);
+    /// while let Some(line) = reader.next_line_ext() {
+    ///     match line? {
+    ///         Line::Complete(_line) => { /* delimiter-terminated */ }
+    ///         Line::Truncated(_chunk) => { /* more of this line follows */ }
+    ///         Line::Unterminated(_line) => { /* final line, no delimiter */ }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_line_ext(&mut self) -> Option<io::Result<Line<'_>>> {
+        self.ext_mode = true;
+
+        if self.pos < self.end_of_complete {
+            let lastpos = self.pos;
+
+            return Some(match memchr(self.delimiter, &self.buf[lastpos..self.end_of_complete]) {
+                Some(nl) => {
+                    self.pos = lastpos + nl + 1;
+                    self.line_len = 0;
+                    Ok(Line::Complete(&self.buf[lastpos..self.pos]))
+                }
+                None => {
+                    self.pos = self.end_of_complete;
+                    self.line_len += self.pos - lastpos;
+
+                    match self.max_line {
+                        Some(max) if self.line_len > max => {
+                            let discarded_so_far = self.pos - lastpos;
+                            self.line_len = 0;
+                            match self.discard_rest_of_line(discarded_so_far) {
+                                Ok(total) => Err(Self::line_too_long_error(total)),
+                                Err(e) => Err(e),
+                            }
+                        }
+                        _ => Ok(Line::Truncated(&self.buf[lastpos..self.pos])),
+                    }
+                }
+            });
+        }
+
+        match self.refill() {
+            Ok(true) => self.next_line_ext(),
+            Ok(false) => {
+                if self.end_of_buffer == self.pos {
+                    None
+                } else {
+                    let lastpos = self.pos;
+                    self.pos = self.end_of_buffer;
+                    self.line_len = 0;
+                    Some(Ok(Line::Unterminated(&self.buf[lastpos..self.pos])))
+                }
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Consume and discard bytes up to and including the next delimiter (or
+    /// EOF), returning the total number of bytes discarded including
+    /// `already_discarded`. Used by `next_line_ext` once a line has grown
+    /// past `max_line`.
+    fn discard_rest_of_line(&mut self, already_discarded: usize) -> io::Result<usize> {
+        let mut discarded = already_discarded;
+
+        loop {
+            if self.pos < self.end_of_complete {
+                let lastpos = self.pos;
+
+                match memchr(self.delimiter, &self.buf[lastpos..self.end_of_complete]) {
+                    Some(nl) => {
+                        self.pos = lastpos + nl + 1;
+                        discarded += self.pos - lastpos;
+                        return Ok(discarded);
+                    }
+                    None => {
+                        self.pos = self.end_of_complete;
+                        discarded += self.pos - lastpos;
+                    }
+                }
+            }
+
+            match self.refill()? {
+                true => continue,
+                false => {
+                    discarded += self.end_of_buffer - self.pos;
+                    self.pos = self.end_of_buffer;
+                    return Ok(discarded);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn line_too_long_error(discarded: usize) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line exceeded max_line, discarded {} bytes", discarded),
+        )
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn line_too_long_error(_discarded: usize) -> io::Error {
+        io::Error::new(io::ErrorKind::Other)
+    }
+
     fn refill(&mut self) -> io::Result<bool> {
         assert!(self.pos == self.end_of_complete);
         assert!(self.end_of_complete <= self.end_of_buffer);
 
         self.pos = 0;
 
-        // Move the start of the next line, if any, to the start of buf
         let fragment_len = self.end_of_buffer - self.end_of_complete;
         if fragment_len > 0 {
-            // unsafe variants of these using ptr::copy/copy_nonoverlapping can
-            // be found in 5ccea2c - they made no appreciable difference.
-            if fragment_len > self.end_of_complete {
-                self.buf.drain(..self.end_of_complete);
-                self.buf.extend(vec![0_u8; self.end_of_complete]);
+            self.buf.copy_within(self.end_of_complete..self.end_of_buffer, 0);
+        }
+        self.end_of_buffer = fragment_len;
+
+        // A byte already read from `inner` by the EOF probe below, carried
+        // over from the previous call so it isn't lost. Fold it in as if it
+        // were freshly read, so it's still scanned for the delimiter.
+        if let Some(b) = self.pending.take() {
+            let lastpos = self.end_of_buffer;
+            self.buf[lastpos] = b;
+            self.end_of_buffer += 1;
+
+            if let Some(nl) = memrchr(self.delimiter, &self.buf[lastpos..self.end_of_buffer]) {
+                self.end_of_complete = cmp::min(self.end_of_buffer, 1 + lastpos + nl);
+                return Ok(true);
             } else {
-                let (start, rest) = self.buf.split_at_mut(self.end_of_complete);
-                start[0..fragment_len].copy_from_slice(&rest[0..fragment_len]);
+                self.end_of_complete = self.end_of_buffer;
             }
-            self.end_of_buffer = fragment_len;
-        } else {
-            self.end_of_buffer = 0;
         }
 
-        // Fill the rest of buf from the underlying IO
-        while self.end_of_buffer < self.buf.len() {
-            // Loop until we find a delimiter or read zero bytes.
-            match self.inner.read(&mut self.buf[self.end_of_buffer..]) {
-                Ok(0) => {
-                    self.end_of_complete = self.end_of_buffer;
+        loop {
+            while self.end_of_buffer < self.buf.len() {
+                match self.inner.read(&mut self.buf[self.end_of_buffer..]) {
+                    Ok(0) => {
+                        self.end_of_complete = self.end_of_buffer;
+                        return Ok(false);
+                    }
+                    Ok(n) => {
+                        let lastpos = self.end_of_buffer;
+                        self.end_of_buffer += n;
+                        if let Some(nl) =
+                            memrchr(self.delimiter, &self.buf[lastpos..self.end_of_buffer])
+                        {
+                            self.end_of_complete = cmp::min(self.end_of_buffer, 1 + lastpos + nl);
+                            return Ok(true);
+                        } else {
+                            self.end_of_complete = self.end_of_buffer;
+                        }
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            match self.grow_to {
+                Some(max) if self.buf.len() < max => {
+                    // Growth is still available: grow and keep reading. The
+                    // next iteration's read naturally resolves whether the
+                    // stream had more to give.
+                    let new_len = cmp::min(self.buf.len().saturating_mul(2), max);
+                    self.grow_buf(new_len);
+                }
+                _ if !self.ext_mode => {
+                    // The buffer is completely full with no delimiter in
+                    // sight and can't grow any further. `next_line`/
+                    // `next_batch` don't distinguish "the stream ended here"
+                    // from "this line just keeps going" — either way they
+                    // hand back the full buffer as the current chunk — so
+                    // there's nothing to disambiguate and no need to pay for
+                    // the probe read below.
                     return Ok(false);
                 }
-                Ok(n) => {
-                    let lastpos = self.end_of_buffer;
-                    self.end_of_buffer += n;
-                    if let Some(nl) =
-                        memrchr(self.delimiter, &self.buf[lastpos..self.end_of_buffer])
-                    {
-                        self.end_of_complete = cmp::min(self.end_of_buffer, 1 + lastpos + nl);
-                        return Ok(true);
-                    } else {
-                        // No delimiter - see if we can read any more.
-                        self.end_of_complete = self.end_of_buffer;
+                _ => {
+                    // `next_line_ext` is in play, so the same situation
+                    // *is* ambiguous: the line might simply continue, or the
+                    // stream might have ended exactly here, and it needs to
+                    // tell `Truncated` from `Unterminated` correctly. Probe
+                    // for one more byte to tell the two apart. If there's
+                    // more, stash it in `pending` rather than losing it; the
+                    // next `refill` call folds it back in.
+                    let mut probe = [0u8];
+                    loop {
+                        match self.inner.read(&mut probe) {
+                            Ok(0) => return Ok(false),
+                            Ok(_) => {
+                                self.pending = Some(probe[0]);
+                                return Ok(true);
+                            }
+                            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                            Err(e) => return Err(e),
+                        }
                     }
                 }
-                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
-                Err(e) => return Err(e),
             }
         }
-
-        // We read through until the end of the buffer.
-        Ok(true)
     }
 
     /// Reset the internal state of the buffer.  Next lines are read from wherever
@@ -293,6 +709,48 @@ impl<R: io::Read> LineReader<R> {
         self.pos = 0;
         self.end_of_buffer = 0;
         self.end_of_complete = 0;
+        self.line_len = 0;
+        self.pending = None;
+        #[cfg(feature = "std")]
+        {
+            self.rev = None;
+        }
+    }
+
+    /// Switch to a new delimiter without discarding (or re-reading) any
+    /// bytes already sitting in the buffer.
+    ///
+    /// Unlike constructing a fresh `LineReader`, this re-scans whatever's
+    /// left of the currently buffered region for the new delimiter, so it's
+    /// safe to call mid-stream when a framing byte changes partway through
+    /// — for example a NUL-delimited header section followed by a
+    /// newline-delimited body read through the same `LineReader`.
+    ///
+    /// ```no_run
+    /// # use linereader::LineReader;
+    /// # use std::io::Cursor;
+    /// # #[cfg(feature = "std")] {
+    /// let mut reader = LineReader::with_delimiter(0, Cursor::new(&b"hdr\0body\nmore\n"[..]));
+    /// assert_eq!(b"hdr\0", reader.next_line().unwrap().unwrap());
+    /// reader.reset_with(b'\n');
+    /// assert_eq!(b"body\n", reader.next_line().unwrap().unwrap());
+    /// # }
+    /// ```
+    pub fn reset_with(&mut self, delimiter: u8) {
+        self.delimiter = delimiter;
+        self.line_len = 0;
+
+        self.end_of_complete = match memrchr(delimiter, &self.buf[self.pos..self.end_of_buffer]) {
+            Some(nl) => self.pos + nl + 1,
+            None => self.pos,
+        };
+    }
+
+    /// Get the delimiter this `LineReader` currently splits on, as set by
+    /// `with_delimiter`/`with_delimiter_and_capacity` or a later
+    /// `reset_with` call.
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
     }
 
     /// Get a reference to the reader.
@@ -310,11 +768,349 @@ impl<R: io::Read> LineReader<R> {
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    /// Turn this `LineReader` into an iterator yielding owned lines.
+    ///
+    /// `next_line` can't implement `Iterator` itself, since the lines it
+    /// returns borrow from `&mut self`.  This adapter allocates a `Vec<u8>`
+    /// per line instead, trading the zero-copy benefit for the ergonomics of
+    /// a plain `for` loop.
+    ///
+    /// ```no_run
+    /// # use linereader::LineReader;
+    /// # use std::fs::File;
+    /// # use std::io;
+    /// # #[cfg(feature = "std")]
+    /// # fn x() -> io::Result<()> {
+    /// for line in LineReader::new(File::open("myfile.txt")?).lines_iter() {
+    ///     let line = line?;  // Vec<u8>
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lines_iter(self) -> Lines<R> {
+        Lines { reader: self }
+    }
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl<R: io::Read> io::Read for LineReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let data = io::BufRead::fill_buf(self)?;
+        let n = cmp::min(data.len(), out.len());
+        out[..n].copy_from_slice(&data[..n]);
+        io::BufRead::consume(self, n);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read> io::BufRead for LineReader<R> {
+    /// Return the currently buffered, delimiter-scanned region, refilling
+    /// from the underlying reader if it's been fully consumed.
+    ///
+    /// This lets `LineReader` stand in for a `BufReader` wherever
+    /// `read_until`, `split`, or other `BufRead` combinators are expected,
+    /// while still sharing the same buffer and `refill` logic as
+    /// `next_line`/`next_batch`.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.end_of_complete {
+            self.refill()?;
+        }
+
+        Ok(&self.buf[self.pos..self.end_of_complete])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.end_of_complete);
+    }
+}
+
+/// An iterator over owned lines, produced by `LineReader::lines_iter` or by
+/// calling `into_iter` on a `LineReader` directly.
+pub struct Lines<R> {
+    reader: LineReader<R>,
+}
+
+impl<R: io::Read> Iterator for Lines<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        match self.reader.next_line() {
+            Some(Ok(line)) => Some(Ok(line.to_vec())),
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
+        }
+    }
+}
+
+impl<R: io::Read> IntoIterator for LineReader<R> {
+    type Item = io::Result<Vec<u8>>;
+    type IntoIter = Lines<R>;
+
+    fn into_iter(self) -> Lines<R> {
+        Lines { reader: self }
+    }
+}
+
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+#[cfg(feature = "std")]
+use std::thread;
+
+/// A reader adaptor that reads its inner reader on a background thread, one
+/// chunk ahead of the caller, so a slow source (a pipe, a network socket) can
+/// be filling the next chunk while `LineReader` is still scanning the
+/// current one for delimiters.
+///
+/// Only available with `std`, since it needs threads and channels. Wrap one
+/// around any reader and hand it to `LineReader` like any other composed
+/// reader:
+///
+/// ```no_run
+/// # use linereader::{LineReader, PrefetchReader};
+/// # use std::fs::File;
+/// # use std::io;
+/// # #[cfg(feature = "std")]
+/// # fn x() -> io::Result<()> {
+/// let mut reader = LineReader::new(PrefetchReader::new(File::open("myfile.txt")?));
+/// while let Some(line) = reader.next_line() {
+///     let line = line?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub struct PrefetchReader {
+    rx: Option<mpsc::Receiver<io::Result<Vec<u8>>>>,
+    handle: Option<thread::JoinHandle<()>>,
+    current: Vec<u8>,
+    pos: usize,
+}
+
+#[cfg(feature = "std")]
+impl PrefetchReader {
+    /// Start prefetching `inner` on a background thread, reading ahead in
+    /// 64 KiB chunks.
+    pub fn new<R: io::Read + Send + 'static>(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, inner)
+    }
+
+    /// Like `new`, but reading ahead in `capacity`-sized chunks.
+    pub fn with_capacity<R: io::Read + Send + 'static>(capacity: usize, mut inner: R) -> Self {
+        // Capacity 1: the sender blocks until the previous chunk has been
+        // taken, so at most one chunk is ever buffered ahead of the reader.
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        let handle = thread::spawn(move || loop {
+            #[allow(clippy::uninit_vec)]
+            let mut chunk = {
+                // SAFETY: the bytes `inner.read()` doesn't fill are dropped
+                // right back off below via `truncate(n)` before the chunk
+                // ever reaches the caller, and `u8` has no invalid bit
+                // patterns to begin with — nothing ever observes the
+                // uninitialized tail.
+                let mut chunk = Vec::with_capacity(capacity);
+                unsafe {
+                    chunk.set_len(capacity);
+                }
+                chunk
+            };
+            let read = loop {
+                match inner.read(&mut chunk) {
+                    Ok(n) => break Ok(n),
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => break Err(e),
+                }
+            };
+
+            let done = !matches!(read, Ok(n) if n > 0);
+            let sent = match read {
+                Ok(n) => {
+                    chunk.truncate(n);
+                    tx.send(Ok(chunk))
+                }
+                Err(e) => tx.send(Err(e)),
+            };
+
+            if sent.is_err() || done {
+                break;
+            }
+        });
+
+        PrefetchReader {
+            rx: Some(rx),
+            handle: Some(handle),
+            current: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Read for PrefetchReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.current.len() {
+            match self.rx.as_ref().unwrap().recv() {
+                Ok(Ok(chunk)) => {
+                    self.current = chunk;
+                    self.pos = 0;
+                }
+                Ok(Err(e)) => return Err(e),
+                // Background thread hit EOF and exited without sending again.
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = cmp::min(out.len(), self.current.len() - self.pos);
+        out[..n].copy_from_slice(&self.current[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for PrefetchReader {
+    fn drop(&mut self) {
+        // Drop the receiver first: if the background thread is blocked on
+        // `send`, this wakes it immediately instead of leaving `join` below
+        // waiting on a thread that's waiting on us.
+        self.rx.take();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// `Seek` has no equivalent in the minimal `no_std` `io` module, so reverse
+// reading is only available with `std`.
+#[cfg(feature = "std")]
+impl<R: io::Read + io::Seek> LineReader<R> {
+    /// Get the next line from the end of the stream, working backwards, an
+    /// IO error, or `None` once the start of the stream is reached.  The
+    /// delimiter is included at the end of any returned slice, unless it
+    /// terminated the file without one.  Named to match `next_line`, just
+    /// walking the stream in the opposite direction.
+    ///
+    /// This reads the same underlying buffer capacity as `next_line`, but
+    /// walks it backwards a block at a time using `seek`, so it only works on
+    /// readers that support seeking (files, not pipes).  Mixing calls to
+    /// `next_line_back` with `next_line`/`next_batch` on the same `LineReader` is
+    /// not supported; the two scans keep separate state.
+    ///
+    /// ```no_run
+    /// # use linereader::LineReader;
+    /// # use std::fs::File;
+    /// # use std::io;
+    /// # #[cfg(feature = "std")]
+    /// # fn x() -> io::Result<()> {
+    /// let mut reader = LineReader::new(File::open("myfile.txt")?);
+    /// while let Some(line) = reader.next_line_back() {
+    ///     let line = line?;  // unwrap io::Result to &[u8]
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_line_back(&mut self) -> Option<io::Result<&[u8]>> {
+        if self.rev.is_none() {
+            let len = match self.inner.seek(io::SeekFrom::End(0)) {
+                Ok(len) => len,
+                Err(e) => return Some(Err(e)),
+            };
+            self.rev = Some(ReverseState::new(len));
+        }
+
+        loop {
+            let mut state = self.rev.take().unwrap();
+
+            if state.done {
+                self.rev = Some(state);
+                return None;
+            }
+
+            if let Some(idx) = memrchr(self.delimiter, &state.block[..state.end]) {
+                let start = idx + 1;
+                let end = state.line_end;
+                state.end = idx;
+                state.line_end = idx + 1;
+
+                if start == end && state.carry.is_empty() {
+                    // This delimiter is the last byte of the previous search
+                    // window, i.e. the block ends exactly on a delimiter:
+                    // there's no new line content after it yet, just the
+                    // boundary for the next one. Keep searching rather than
+                    // handing back an empty line.
+                    self.rev = Some(state);
+                    continue;
+                }
+
+                if state.carry.is_empty() {
+                    self.rev = Some(state);
+                    return Some(Ok(&self.rev.as_ref().unwrap().block[start..end]));
+                } else {
+                    let mut line = mem::take(&mut state.line);
+                    line.clear();
+                    line.extend_from_slice(&state.block[start..end]);
+                    line.extend_from_slice(&state.carry);
+                    state.carry.clear();
+                    state.line = line;
+                    self.rev = Some(state);
+                    return Some(Ok(&self.rev.as_ref().unwrap().line));
+                }
+            }
+
+            // No delimiter left in this block: stash it (including the
+            // boundary of any line still being assembled) as the start of a
+            // carried-over line and go fetch an earlier one.
+            if state.line_end > 0 {
+                let mut carry = Vec::with_capacity(state.line_end + state.carry.len());
+                carry.extend_from_slice(&state.block[..state.line_end]);
+                carry.extend_from_slice(&state.carry);
+                state.carry = carry;
+                state.end = 0;
+                state.line_end = 0;
+            }
+
+            if state.offset == 0 {
+                state.done = true;
+                if state.carry.is_empty() {
+                    self.rev = Some(state);
+                    return None;
+                }
+                state.line = mem::take(&mut state.carry);
+                self.rev = Some(state);
+                return Some(Ok(&self.rev.as_ref().unwrap().line));
+            }
+
+            let capacity = cmp::max(self.buf.capacity(), 1) as u64;
+            let block_len = cmp::min(capacity, state.offset) as usize;
+            let read_at = state.offset - block_len as u64;
+
+            if let Err(e) = self.inner.seek(io::SeekFrom::Start(read_at)) {
+                self.rev = Some(state);
+                return Some(Err(e));
+            }
+
+            let mut block = vec![0u8; block_len];
+            if let Err(e) = self.inner.read_exact(&mut block) {
+                self.rev = Some(state);
+                return Some(Err(e));
+            }
+
+            state.block = block;
+            state.end = block_len;
+            state.line_end = block_len;
+            state.offset = read_at;
+            self.rev = Some(state);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use LineReader;
+    use Line;
 
     #[test]
     fn test_next_line() {
@@ -347,6 +1143,77 @@ mod tests {
         assert_eq!(b"7hhhhhh7", reader.next_batch().unwrap().unwrap());
     }
 
+    #[test]
+    fn test_next_line_ext() {
+        let buf: &[u8] = b"0a0\n1bb1\n2ccc2\n3dddd3\n4eeeee4\n5ffffffff5\n6ggggg6\n7hhhhhh7";
+        let mut reader = LineReader::with_capacity(8, buf);
+
+        assert_eq!(Line::Complete(b"0a0\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"1bb1\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"2ccc2\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"3dddd3\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"4eeeee4\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Truncated(b"5fffffff"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"f5\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Complete(b"6ggggg6\n"), reader.next_line_ext().unwrap().unwrap());
+        assert_eq!(Line::Unterminated(b"7hhhhhh7"), reader.next_line_ext().unwrap().unwrap());
+        assert!(reader.next_line_ext().is_none());
+    }
+
+    #[test]
+    fn test_with_max_line() {
+        let buf: &[u8] = b"short\nthis line is much too long\nshort again\n";
+        let mut reader = LineReader::with_capacity(8, buf).with_max_line(5);
+
+        assert_eq!(b"short\n", reader.next_line().unwrap().unwrap());
+        assert!(reader.next_line_ext().unwrap().is_err());
+        assert_eq!(b"short ag", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"ain\n", reader.next_line().unwrap().unwrap());
+    }
+
+    #[test]
+    fn test_with_growth() {
+        let buf: &[u8] = b"short\nthis line is much too long\nshort again\n";
+        let mut reader = LineReader::with_capacity(8, buf).with_growth(64);
+
+        assert_eq!(b"short\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(
+            b"this line is much too long\n",
+            reader.next_line().unwrap().unwrap()
+        );
+        assert_eq!(b"short again\n", reader.next_line().unwrap().unwrap());
+        assert!(reader.next_line().is_none());
+    }
+
+    #[test]
+    fn test_reset_with() {
+        let buf: &[u8] = b"hdr1\0hdr2\0body1\nbody2\n";
+        let mut reader = LineReader::with_delimiter(0, buf);
+        assert_eq!(0, reader.delimiter());
+
+        assert_eq!(b"hdr1\0", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"hdr2\0", reader.next_line().unwrap().unwrap());
+
+        reader.reset_with(b'\n');
+        assert_eq!(b'\n', reader.delimiter());
+
+        assert_eq!(b"body1\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"body2\n", reader.next_line().unwrap().unwrap());
+        assert!(reader.next_line().is_none());
+    }
+
+    #[test]
+    fn test_stacked_line_readers() {
+        let buf: &[u8] = b"foo\nbar\nbaz";
+        let inner = LineReader::with_capacity(4, buf);
+        let mut outer = LineReader::with_capacity(64, inner);
+
+        assert_eq!(b"foo\n", outer.next_line().unwrap().unwrap());
+        assert_eq!(b"bar\n", outer.next_line().unwrap().unwrap());
+        assert_eq!(b"baz", outer.next_line().unwrap().unwrap());
+        assert!(outer.next_line().is_none());
+    }
+
     #[test]
     fn test_for_each() {
         let buf: &[u8] = b"f\nba\nbaz\n";
@@ -361,6 +1228,85 @@ mod tests {
         reader.for_each(|l| { assert_eq!(l.len(), 2); Ok(false) }).unwrap();
     }
 
+    #[test]
+    fn test_next_line_back() {
+        let buf: &[u8] = b"0a0\n1bb1\n2ccc2\n3dddd3\n4eeeee4\n5ffffffff5\n6ggggg6\n7hhhhhh7";
+        let mut reader = LineReader::with_capacity(8, Cursor::new(buf));
+
+        assert_eq!(b"7hhhhhh7", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"6ggggg6\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"5ffffffff5\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"4eeeee4\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"3dddd3\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"2ccc2\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"1bb1\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"0a0\n", reader.next_line_back().unwrap().unwrap());
+        assert!(reader.next_line_back().is_none());
+    }
+
+    #[test]
+    fn test_next_line_back_trailing_delimiter() {
+        let buf: &[u8] = b"foo\nbar\nbaz\n";
+        let mut reader = LineReader::new(Cursor::new(buf));
+
+        assert_eq!(b"baz\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"bar\n", reader.next_line_back().unwrap().unwrap());
+        assert_eq!(b"foo\n", reader.next_line_back().unwrap().unwrap());
+        assert!(reader.next_line_back().is_none());
+    }
+
+    #[test]
+    fn test_lines_iter() {
+        let buf: &[u8] = b"foo\nbar\nbaz";
+        let reader = LineReader::new(buf);
+
+        let lines: Vec<Vec<u8>> = reader.lines_iter().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, vec![b"foo\n".to_vec(), b"bar\n".to_vec(), b"baz".to_vec()]);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let buf: &[u8] = b"foo\nbar\nbaz";
+        let reader = LineReader::new(buf);
+
+        let lines: Vec<Vec<u8>> = reader.into_iter().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, vec![b"foo\n".to_vec(), b"bar\n".to_vec(), b"baz".to_vec()]);
+    }
+
+    #[test]
+    fn test_prefetch_reader() {
+        use PrefetchReader;
+
+        let buf: &[u8] = b"foo\nbar\nbaz";
+        let mut reader = LineReader::with_capacity(4, PrefetchReader::with_capacity(4, buf));
+
+        assert_eq!(b"foo\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"bar\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"baz", reader.next_line().unwrap().unwrap());
+        assert!(reader.next_line().is_none());
+    }
+
+    #[test]
+    fn test_bufread() {
+        let buf: &[u8] = b"foo\nbar\nbaz\n";
+        let mut reader = LineReader::with_capacity(4, buf);
+
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line).unwrap();
+        assert_eq!(line, b"foo\n");
+
+        line.clear();
+        reader.read_until(b'\n', &mut line).unwrap();
+        assert_eq!(line, b"bar\n");
+
+        line.clear();
+        reader.read_until(b'\n', &mut line).unwrap();
+        assert_eq!(line, b"baz\n");
+
+        line.clear();
+        assert_eq!(reader.read_until(b'\n', &mut line).unwrap(), 0);
+    }
+
     extern crate rand;
     use std::io::BufRead;
     use std::io::{Cursor, Read};
@@ -395,3 +1341,50 @@ mod tests {
         }
     }
 }
+
+/// Coverage for the no_std path: `mod tests` above exercises `std`'s
+/// `Read`/`BufRead`, but that leaves the custom `io::Read` trait and the
+/// `refill`/scan logic running against it — this crate's entire reason for
+/// existing without `std` — with no test coverage of its own.
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use io::{Read, Result};
+    use LineReader;
+
+    /// A bare `io::Read` source backed by nothing but a byte slice, so these
+    /// tests exercise only the crate's own no_std `io` module.
+    struct SliceSource<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Read for SliceSource<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = if buf.len() < self.data.len() {
+                buf.len()
+            } else {
+                self.data.len()
+            };
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_next_line() {
+        let mut reader = LineReader::with_capacity(4, SliceSource { data: b"foo\nbar\nbaz" });
+
+        assert_eq!(b"foo\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"bar\n", reader.next_line().unwrap().unwrap());
+        assert_eq!(b"baz", reader.next_line().unwrap().unwrap());
+        assert!(reader.next_line().is_none());
+    }
+
+    #[test]
+    fn test_next_batch() {
+        let mut reader = LineReader::with_capacity(64, SliceSource { data: b"foo\nbar\nbaz\n" });
+
+        assert_eq!(b"foo\nbar\nbaz\n", reader.next_batch().unwrap().unwrap());
+        assert!(reader.next_batch().is_none());
+    }
+}